@@ -0,0 +1,210 @@
+use crate::project::manifest::FeatureName;
+use crate::task::{
+    resolve_chain, run_graph, OutputMode, Task, TaskCache, TaskGraph, TaskName, TaskStatus,
+    UnresolvedEnvVar,
+};
+use crate::Project;
+use clap::Parser;
+use indexmap::IndexMap;
+use itertools::Itertools;
+use rattler_conda_types::Platform;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Run a task, resolving its `depends-on` chain into a graph and executing it (and every
+/// dependency it transitively needs) to completion.
+#[derive(Parser, Debug)]
+#[clap(arg_required_else_help = true)]
+pub struct Args {
+    /// The task to run
+    pub task: TaskName,
+
+    /// A value for one of the task's declared arguments, use `--arg name=value` multiple times
+    /// for more than one
+    #[arg(long = "arg", value_parser = parse_key_val)]
+    pub args: Vec<(String, String)>,
+
+    /// The maximum number of tasks to run concurrently, defaulting to available parallelism
+    #[arg(long, short)]
+    pub jobs: Option<usize>,
+
+    /// Run every task even if its cached fingerprint says it's up to date
+    #[arg(long)]
+    pub force: bool,
+
+    /// The platform to run the task for
+    #[arg(long, short)]
+    pub platform: Option<Platform>,
+
+    /// The feature whose tasks should be considered
+    #[arg(long, short)]
+    pub feature: Option<String>,
+
+    /// The path to 'pixi.toml' or 'pyproject.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+}
+
+/// Parse a single `name=value` pair
+fn parse_key_val(s: &str) -> Result<(String, String), Box<dyn Error + Send + Sync + 'static>> {
+    let pos = s
+        .find('=')
+        .ok_or_else(|| format!("invalid name=value: no `=` found in `{}`", s))?;
+    Ok((s[..pos].to_string(), s[pos + 1..].to_string()))
+}
+
+/// Builds the platform shell invocation for `rendered_cmd`.
+fn shell_command(rendered_cmd: &str) -> Command {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    command.arg(rendered_cmd);
+    command
+}
+
+/// Runs `rendered_cmd` for `name` according to `mode`: streamed tasks inherit the parent's
+/// stdout/stderr directly, buffered tasks have their combined output captured and printed with
+/// a `[name]` prefix once they finish.
+fn run_task(name: &TaskName, rendered_cmd: &str, mode: OutputMode) -> bool {
+    match mode {
+        OutputMode::Streamed => {
+            eprintln!(
+                "{}Running `{}`: {}",
+                console::style(console::Emoji("▶ ", "> ")).blue(),
+                name.fancy_display(),
+                rendered_cmd,
+            );
+            match shell_command(rendered_cmd).status() {
+                Ok(status) => status.success(),
+                Err(err) => {
+                    eprintln!("failed to spawn `{rendered_cmd}`: {err}");
+                    false
+                }
+            }
+        }
+        OutputMode::Buffered => match shell_command(rendered_cmd).output() {
+            Ok(output) => {
+                let prefix = format!("[{}] ", name.as_str());
+                for line in String::from_utf8_lossy(&output.stdout).lines() {
+                    eprintln!("{prefix}{line}");
+                }
+                for line in String::from_utf8_lossy(&output.stderr).lines() {
+                    eprintln!("{prefix}{line}");
+                }
+                output.status.success()
+            }
+            Err(err) => {
+                eprintln!("failed to spawn `{rendered_cmd}`: {err}");
+                false
+            }
+        },
+    }
+}
+
+pub fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    let feature = args
+        .feature
+        .clone()
+        .map_or(FeatureName::Default, FeatureName::Named);
+    let tasks: IndexMap<TaskName, Task> = project.manifest.tasks(args.platform, &feature)?;
+
+    let values: IndexMap<String, String> = args.args.into_iter().collect();
+    let chain = resolve_chain(args.task.clone(), values, &tasks)
+        .map_err(|err| miette::miette!("{err}"))?;
+
+    // Only the tasks actually needed for this invocation (the root plus its transitive
+    // dependencies) are scheduled, each with the argument values `resolve_chain` forwarded to it.
+    let mut subset: IndexMap<TaskName, Task> = IndexMap::new();
+    let mut chain_args: IndexMap<TaskName, IndexMap<String, String>> = IndexMap::new();
+    for task_ref in &chain {
+        if let Some(task) = tasks.get(&task_ref.id) {
+            subset.insert(task_ref.id.clone(), task.clone());
+        }
+        chain_args.insert(task_ref.id.clone(), task_ref.args.clone());
+    }
+    let graph = TaskGraph::from_tasks(subset.iter());
+
+    let cache_path = project.root().join(".pixi").join("task-cache.json");
+    let cache = Mutex::new(TaskCache::load(&cache_path));
+
+    let outcome = run_graph(&graph, args.jobs, |name, mode| {
+        let execute = match subset.get(name) {
+            Some(Task::Execute(execute)) => execute,
+            // Plain tasks have no args to render, so their command runs as written.
+            Some(Task::Plain(cmd)) => return run_task(name, cmd, mode),
+            // Aliases have nothing to run directly.
+            _ => return true,
+        };
+        let values = chain_args.get(name).cloned().unwrap_or_default();
+        // TODO: look variables up in the activated pixi environment once environment activation
+        // is wired in here; for now the outer shell is the only source either way.
+        let env_fn = |var: &str| {
+            std::env::var(var).map_err(|_| UnresolvedEnvVar {
+                name: var.to_string(),
+            })
+        };
+        let rendered_cmd = match execute.prepare_cmd(&values, env_fn) {
+            Ok(cmd) => cmd,
+            Err(err) => {
+                eprintln!("task `{}` failed to prepare: {err}", name.fancy_display());
+                return false;
+            }
+        };
+
+        let status = cache
+            .lock()
+            .unwrap()
+            .status(project.root(), name, execute, &rendered_cmd, args.force);
+        match status {
+            Ok(TaskStatus::UpToDate) => {
+                eprintln!("{} is up to date, skipping", name.fancy_display());
+                return true;
+            }
+            Ok(TaskStatus::Stale) => {}
+            Err(err) => {
+                eprintln!("failed to check cache for `{}`: {err}", name.fancy_display());
+            }
+        }
+
+        if !run_task(name, &rendered_cmd, mode) {
+            return false;
+        }
+
+        if let Err(err) = cache.lock().unwrap().record_and_invalidate_downstream(
+            project.root(),
+            &graph,
+            name,
+            execute,
+            &rendered_cmd,
+        ) {
+            eprintln!("failed to update cache for `{}`: {err}", name.fancy_display());
+        }
+        true
+    })
+    .map_err(|err| miette::miette!("{err}"))?;
+
+    if let Err(err) = cache.into_inner().unwrap().save(&cache_path) {
+        eprintln!("failed to write task cache: {err}");
+    }
+
+    if let Some(failed) = &outcome.failed {
+        if !outcome.skipped.is_empty() {
+            eprintln!(
+                "skipped (not started before a task failed): {}",
+                outcome.skipped.iter().map(TaskName::as_str).join(", ")
+            );
+        }
+        miette::bail!("task `{}` failed", failed.fancy_display());
+    }
+
+    Ok(())
+}
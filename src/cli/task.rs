@@ -2,12 +2,13 @@ use crate::project::manifest::EnvironmentName;
 use crate::project::manifest::FeatureName;
 use crate::project::virtual_packages::verify_current_platform_has_required_virtual_packages;
 use crate::project::Environment;
-use crate::task::{quote, Alias, CmdArgs, Execute, Task, TaskName};
+use crate::task::{quote, Alias, ArgSpec, CmdArgs, Execute, Task, TaskName};
 use crate::Project;
 use clap::Parser;
 use indexmap::IndexMap;
 use itertools::Itertools;
 use rattler_conda_types::Platform;
+use serde::Serialize;
 use std::collections::HashSet;
 use std::error::Error;
 use std::io;
@@ -27,6 +28,10 @@ pub enum Operation {
     #[clap(visible_alias = "rm", alias = "r")]
     Remove(RemoveArgs),
 
+    /// Update a task in the project
+    #[clap(visible_alias = "u")]
+    Update(UpdateArgs),
+
     /// Alias another specific command
     #[clap(alias = "@")]
     Alias(AliasArgs),
@@ -85,6 +90,22 @@ pub struct AddArgs {
     /// Isolate the task from the shell environment, and only use the pixi environment to run the task
     #[arg(long)]
     pub clean_env: bool,
+
+    /// Declare a named argument that can be substituted into the command with `{{ name }}`, use
+    /// `--arg name=default` to give it a default value, or `--arg name` to require the caller to
+    /// always supply one
+    #[arg(long = "arg", value_parser = parse_arg_decl)]
+    pub args: Vec<(String, Option<String>)>,
+
+    /// A glob pattern of files that should be watched for changes before this command is run,
+    /// use --inputs multiple times to add more than one pattern
+    #[arg(long)]
+    pub inputs: Option<Vec<String>>,
+
+    /// A glob pattern of files that are generated by this command, used together with `inputs`
+    /// to skip the task when nothing relevant has changed since the last run
+    #[arg(long)]
+    pub outputs: Option<Vec<String>>,
 }
 
 /// Parse a single key-value pair
@@ -97,6 +118,67 @@ fn parse_key_val(s: &str) -> Result<(String, String), Box<dyn Error + Send + Syn
     Ok((key, value))
 }
 
+/// Parse a `name[=default]` argument declaration
+fn parse_arg_decl(
+    s: &str,
+) -> Result<(String, Option<String>), Box<dyn Error + Send + Sync + 'static>> {
+    match s.split_once('=') {
+        Some((name, default)) => Ok((name.to_string(), Some(default.to_string()))),
+        None => Ok((s.to_string(), None)),
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+#[clap(arg_required_else_help = true)]
+pub struct UpdateArgs {
+    /// Task name to update
+    pub name: TaskName,
+
+    /// Replace the command that is run
+    #[arg(long)]
+    pub cmd: Option<String>,
+
+    /// Add a dependency to run before this task, use --add-depends-on multiple times for more
+    /// than one
+    #[arg(long, num_args = 1..)]
+    pub add_depends_on: Vec<TaskName>,
+
+    /// Remove a dependency from this task, use --remove-depends-on multiple times for more than
+    /// one
+    #[arg(long, num_args = 1..)]
+    pub remove_depends_on: Vec<TaskName>,
+
+    /// Set an environment variable, use --set-env key=value multiple times for more than one
+    #[arg(long, value_parser = parse_key_val)]
+    pub set_env: Vec<(String, String)>,
+
+    /// Unset a previously set environment variable, use --unset-env multiple times for more
+    /// than one
+    #[arg(long)]
+    pub unset_env: Vec<String>,
+
+    /// Change the working directory relative to the root of the project
+    #[arg(long)]
+    pub cwd: Option<PathBuf>,
+
+    /// Isolate the task from the shell environment, and only use the pixi environment to run
+    /// the task
+    #[arg(long, conflicts_with = "no_clean_env")]
+    pub clean_env: bool,
+
+    /// Stop isolating the task from the shell environment
+    #[arg(long)]
+    pub no_clean_env: bool,
+
+    /// The platform for which the task should be updated
+    #[arg(long, short)]
+    pub platform: Option<Platform>,
+
+    /// The feature for which the task should be updated
+    #[arg(long, short)]
+    pub feature: Option<String>,
+}
+
 #[derive(Parser, Debug, Clone)]
 #[clap(arg_required_else_help = true)]
 pub struct AliasArgs {
@@ -128,6 +210,11 @@ pub struct ListArgs {
     /// If not specified, the default environment is used.
     #[arg(long, short)]
     pub environment: Option<String>,
+
+    /// Output the full resolved task graph as JSON, for consumption by editors, CI, or other
+    /// external tooling
+    #[arg(long)]
+    pub json: bool,
 }
 
 impl From<AddArgs> for Task {
@@ -150,7 +237,13 @@ impl From<AddArgs> for Task {
         // complex, or alias command.
         if cmd_args.trim().is_empty() && !depends_on.is_empty() {
             Self::Alias(Alias { depends_on })
-        } else if depends_on.is_empty() && value.cwd.is_none() && value.env.is_empty() {
+        } else if depends_on.is_empty()
+            && value.cwd.is_none()
+            && value.env.is_empty()
+            && value.args.is_empty()
+            && value.inputs.is_none()
+            && value.outputs.is_none()
+        {
             Self::Plain(cmd_args)
         } else {
             let clean_env = value.clean_env;
@@ -164,11 +257,21 @@ impl From<AddArgs> for Task {
                 }
                 Some(env)
             };
+            let args = if value.args.is_empty() {
+                None
+            } else {
+                let mut args = IndexMap::new();
+                for (name, default) in value.args {
+                    args.insert(name, ArgSpec { default });
+                }
+                Some(args)
+            };
             Self::Execute(Execute {
                 cmd: CmdArgs::Single(cmd_args),
                 depends_on,
-                inputs: None,
-                outputs: None,
+                args,
+                inputs: value.inputs,
+                outputs: value.outputs,
                 cwd,
                 env,
                 clean_env,
@@ -185,6 +288,97 @@ impl From<AliasArgs> for Task {
     }
 }
 
+/// Applies the depends-on additions/removals in `args` to `depends_on`, preserving the
+/// existing order and ignoring duplicate additions.
+fn apply_depends_on(depends_on: &mut Vec<TaskName>, args: &UpdateArgs) {
+    depends_on.retain(|name| !args.remove_depends_on.contains(name));
+    for name in &args.add_depends_on {
+        if !depends_on.contains(name) {
+            depends_on.push(name.clone());
+        }
+    }
+}
+
+/// Patches an existing task in place according to `args`, touching only the fields that were
+/// explicitly specified and leaving everything else (including `inputs`/`outputs`) untouched.
+fn apply_update(existing: Task, args: &UpdateArgs) -> miette::Result<Task> {
+    if let Task::Alias(mut alias) = existing {
+        if args.cmd.is_some() {
+            miette::bail!(
+                "task `{}` is an alias and has no command to update",
+                args.name.fancy_display()
+            );
+        }
+        if args.cwd.is_some()
+            || !args.set_env.is_empty()
+            || !args.unset_env.is_empty()
+            || args.clean_env
+            || args.no_clean_env
+        {
+            miette::bail!(
+                "task `{}` is an alias and has no `cwd`, `env` or `clean-env` to update",
+                args.name.fancy_display()
+            );
+        }
+        apply_depends_on(&mut alias.depends_on, args);
+        return Ok(Task::Alias(alias));
+    }
+
+    let mut execute = match existing {
+        Task::Plain(cmd) => Execute {
+            cmd: CmdArgs::Single(cmd),
+            depends_on: Vec::new(),
+            args: None,
+            inputs: None,
+            outputs: None,
+            cwd: None,
+            env: None,
+            clean_env: false,
+        },
+        Task::Execute(execute) => execute,
+        Task::Alias(_) => unreachable!("handled above"),
+        Task::Custom => miette::bail!("cannot update a custom task"),
+    };
+
+    if let Some(cmd) = &args.cmd {
+        execute.cmd = CmdArgs::Single(cmd.clone());
+    }
+    apply_depends_on(&mut execute.depends_on, args);
+    if !args.set_env.is_empty() || !args.unset_env.is_empty() {
+        let mut env = execute.env.unwrap_or_default();
+        for key in &args.unset_env {
+            env.shift_remove(key);
+        }
+        for (key, value) in &args.set_env {
+            env.insert(key.clone(), value.clone());
+        }
+        execute.env = if env.is_empty() { None } else { Some(env) };
+    }
+    if let Some(cwd) = &args.cwd {
+        execute.cwd = Some(cwd.clone());
+    }
+    if args.clean_env {
+        execute.clean_env = true;
+    } else if args.no_clean_env {
+        execute.clean_env = false;
+    }
+
+    // Collapse back down to a plain command when nothing complex is left, mirroring
+    // `From<AddArgs> for Task`.
+    if execute.depends_on.is_empty()
+        && execute.args.is_none()
+        && execute.inputs.is_none()
+        && execute.outputs.is_none()
+        && execute.cwd.is_none()
+        && execute.env.is_none()
+        && !execute.clean_env
+    {
+        Ok(Task::Plain(execute.cmd.as_single_string()))
+    } else {
+        Ok(Task::Execute(execute))
+    }
+}
+
 /// Interact with tasks in the project
 #[derive(Parser, Debug)]
 #[clap(trailing_var_arg = true, arg_required_else_help = true)]
@@ -203,6 +397,112 @@ fn print_heading(value: &str) {
     eprintln!("{}\n{:-<2$}", bold.apply_to(value), "", value.len(),);
 }
 
+/// The full resolved task graph of a project, grouped by environment, for `pixi task list --json`.
+#[derive(Debug, Serialize)]
+struct TaskListOutput {
+    environments: Vec<TaskListEnvironment>,
+}
+
+#[derive(Debug, Serialize)]
+struct TaskListEnvironment {
+    environment: String,
+    tasks: Vec<TaskListEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct TaskListEntry {
+    name: String,
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<String>,
+    #[serde(rename = "depends-on", skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    env: IndexMap<String, String>,
+    #[serde(rename = "clean-env")]
+    clean_env: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inputs: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outputs: Option<Vec<String>>,
+}
+
+impl TaskListEntry {
+    fn from_task(name: &TaskName, task: &Task) -> Self {
+        let (kind, command, depends_on, cwd, env, clean_env, inputs, outputs) = match task {
+            Task::Plain(cmd) => (
+                "plain",
+                Some(cmd.clone()),
+                Vec::new(),
+                None,
+                IndexMap::new(),
+                false,
+                None,
+                None,
+            ),
+            Task::Execute(execute) => (
+                "execute",
+                Some(execute.cmd.as_single_string()),
+                execute.depends_on.iter().map(ToString::to_string).collect(),
+                execute.cwd.as_ref().map(|cwd| cwd.to_string_lossy().to_string()),
+                execute.env.clone().unwrap_or_default(),
+                execute.clean_env,
+                execute.inputs.clone(),
+                execute.outputs.clone(),
+            ),
+            Task::Alias(alias) => (
+                "alias",
+                None,
+                alias.depends_on.iter().map(ToString::to_string).collect(),
+                None,
+                IndexMap::new(),
+                false,
+                None,
+                None,
+            ),
+            Task::Custom => ("custom", None, Vec::new(), None, IndexMap::new(), false, None, None),
+        };
+        Self {
+            name: name.as_str().to_string(),
+            kind,
+            command,
+            depends_on,
+            cwd,
+            env,
+            clean_env,
+            inputs,
+            outputs,
+        }
+    }
+}
+
+/// Prints the full resolved task graph of `envs` as JSON, driven from the same
+/// [`Environment::get_filtered_tasks`] path used by the human-readable list so that
+/// platform/virtual-package filtering stays consistent.
+fn print_tasks_json(envs: Vec<Environment>) -> miette::Result<()> {
+    let mut environments = Vec::new();
+    for env in envs {
+        let tasks = env
+            .get_filtered_tasks()
+            .into_iter()
+            .sorted()
+            .filter_map(|name| env.task(&name).map(|task| TaskListEntry::from_task(&name, &task)))
+            .collect();
+        environments.push(TaskListEnvironment {
+            environment: env.name().to_string(),
+            tasks,
+        });
+    }
+    let output = TaskListOutput { environments };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&output).expect("TaskListOutput is always serializable")
+    );
+    Ok(())
+}
+
 fn print_tasks_per_env(envs: Vec<Environment>) -> io::Result<()> {
     let mut writer = tabwriter::TabWriter::new(stdout());
     for env in envs {
@@ -225,6 +525,7 @@ fn print_tasks_per_env(envs: Vec<Environment>) -> io::Result<()> {
 
 pub fn execute(args: Args) -> miette::Result<()> {
     let mut project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    let manifest_path = args.manifest_path.clone();
     match args.operation {
         Operation::Add(args) => {
             let name = &args.name;
@@ -306,6 +607,45 @@ pub fn execute(args: Args) -> miette::Result<()> {
                 );
             }
         }
+        Operation::Update(args) => {
+            let name = args.name.clone();
+            let feature = args
+                .feature
+                .clone()
+                .map_or(FeatureName::Default, FeatureName::Named);
+            let existing = project
+                .manifest
+                .tasks(args.platform, &feature)?
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| {
+                    if let Some(platform) = args.platform {
+                        miette::miette!(
+                            "task `{}` does not exist on {}",
+                            name.fancy_display(),
+                            platform.as_str(),
+                        )
+                    } else {
+                        miette::miette!(
+                            "task `{}` does not exist for the `{}` feature",
+                            name.fancy_display(),
+                            feature,
+                        )
+                    }
+                })?;
+
+            let task = apply_update(existing, &args)?;
+            project
+                .manifest
+                .update_task(name.clone(), task.clone(), args.platform, &feature)?;
+            project.save()?;
+            eprintln!(
+                "{}Updated task `{}`: {}",
+                console::style(console::Emoji("✔ ", "+")).green(),
+                name.fancy_display().bold(),
+                task,
+            );
+        }
         Operation::Alias(args) => {
             let name = &args.alias;
             let task: Task = args.clone().into();
@@ -334,6 +674,23 @@ pub fn execute(args: Args) -> miette::Result<()> {
                         .ok_or_else(|| miette::miette!("unknown environment '{n}'"))
                 })
                 .transpose()?;
+
+            if args.json {
+                let envs: Vec<Environment> = match &explicit_environment {
+                    Some(env) => vec![env.clone()],
+                    None => project
+                        .environments()
+                        .into_iter()
+                        .filter(|env| {
+                            verify_current_platform_has_required_virtual_packages(env).is_ok()
+                        })
+                        .collect(),
+                };
+                print_tasks_json(envs)?;
+                Project::warn_on_discovered_from_env(manifest_path.as_deref());
+                return Ok(());
+            }
+
             let available_tasks: HashSet<TaskName> =
                 if let Some(explicit_environment) = explicit_environment {
                     explicit_environment.get_filtered_tasks()
@@ -402,12 +759,29 @@ impl From<Task> for Item {
                         )),
                     );
                 }
+                if let Some(args) = process.args {
+                    let mut args_table = Table::new().into_inline_table();
+                    for (name, spec) in args {
+                        let mut spec_table = Table::new().into_inline_table();
+                        if let Some(default) = spec.default {
+                            spec_table.insert("default", default.into());
+                        }
+                        args_table.insert(&name, Value::InlineTable(spec_table));
+                    }
+                    table.insert("args", Value::InlineTable(args_table));
+                }
                 if let Some(cwd) = process.cwd {
                     table.insert("cwd", cwd.to_string_lossy().to_string().into());
                 }
                 if let Some(env) = process.env {
                     table.insert("env", Value::InlineTable(env.into_iter().collect()));
                 }
+                if let Some(inputs) = process.inputs {
+                    table.insert("inputs", Value::Array(Array::from_iter(inputs)));
+                }
+                if let Some(outputs) = process.outputs {
+                    table.insert("outputs", Value::Array(Array::from_iter(outputs)));
+                }
                 Item::Value(Value::InlineTable(table))
             }
             Task::Alias(alias) => {
@@ -428,3 +802,153 @@ impl From<Task> for Item {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(s: &str) -> TaskName {
+        TaskName::from(s)
+    }
+
+    fn update_args(name: TaskName) -> UpdateArgs {
+        UpdateArgs {
+            name,
+            cmd: None,
+            add_depends_on: Vec::new(),
+            remove_depends_on: Vec::new(),
+            set_env: Vec::new(),
+            unset_env: Vec::new(),
+            cwd: None,
+            clean_env: false,
+            no_clean_env: false,
+            platform: None,
+            feature: None,
+        }
+    }
+
+    #[test]
+    fn apply_update_replaces_the_command() {
+        let mut args = update_args(name("build"));
+        args.cmd = Some("make all".to_string());
+
+        let task = apply_update(Task::Plain("make".to_string()), &args).unwrap();
+        assert_eq!(task, Task::Plain("make all".to_string()));
+    }
+
+    #[test]
+    fn apply_update_adds_and_removes_depends_on() {
+        let mut args = update_args(name("test"));
+        args.add_depends_on = vec![name("lint")];
+        args.remove_depends_on = vec![name("build")];
+
+        let existing = Task::Execute(Execute {
+            cmd: CmdArgs::Single("pytest".to_string()),
+            depends_on: vec![name("build")],
+            args: None,
+            inputs: None,
+            outputs: None,
+            cwd: None,
+            env: None,
+            clean_env: false,
+        });
+
+        let Task::Execute(execute) = apply_update(existing, &args).unwrap() else {
+            panic!("still has depends-on, so it stays an Execute task");
+        };
+        assert_eq!(execute.depends_on, vec![name("lint")]);
+    }
+
+    #[test]
+    fn apply_update_sets_and_unsets_env() {
+        let mut args = update_args(name("build"));
+        args.set_env = vec![("NEW".to_string(), "1".to_string())];
+        args.unset_env = vec!["OLD".to_string()];
+
+        let mut env = IndexMap::new();
+        env.insert("OLD".to_string(), "0".to_string());
+        let existing = Task::Execute(Execute {
+            cmd: CmdArgs::Single("make".to_string()),
+            depends_on: Vec::new(),
+            args: None,
+            inputs: None,
+            outputs: None,
+            cwd: None,
+            env: Some(env),
+            clean_env: false,
+        });
+
+        let Task::Execute(execute) = apply_update(existing, &args).unwrap() else {
+            panic!("still has env, so it stays an Execute task");
+        };
+        assert_eq!(
+            execute.env,
+            Some(IndexMap::from([("NEW".to_string(), "1".to_string())]))
+        );
+    }
+
+    #[test]
+    fn apply_update_toggles_clean_env() {
+        let mut args = update_args(name("build"));
+        args.clean_env = true;
+
+        let existing = Task::Plain("make".to_string());
+        let Task::Execute(execute) = apply_update(existing, &args).unwrap() else {
+            panic!("clean_env = true means it's no longer a plain command");
+        };
+        assert!(execute.clean_env);
+    }
+
+    #[test]
+    fn apply_update_collapses_back_to_plain_when_nothing_complex_is_left() {
+        let mut args = update_args(name("build"));
+        args.remove_depends_on = vec![name("setup")];
+
+        let existing = Task::Execute(Execute {
+            cmd: CmdArgs::Single("make".to_string()),
+            depends_on: vec![name("setup")],
+            args: None,
+            inputs: None,
+            outputs: None,
+            cwd: None,
+            env: None,
+            clean_env: false,
+        });
+
+        let task = apply_update(existing, &args).unwrap();
+        assert_eq!(task, Task::Plain("make".to_string()));
+    }
+
+    #[test]
+    fn apply_update_rejects_cwd_change_on_an_alias() {
+        let mut args = update_args(name("ci"));
+        args.cwd = Some(PathBuf::from("subdir"));
+
+        let existing = Task::Alias(Alias {
+            depends_on: vec![name("build")],
+        });
+        let err = apply_update(existing, &args).unwrap_err();
+        assert!(err.to_string().contains("alias"));
+    }
+
+    #[test]
+    fn task_list_entry_from_task_serializes_kebab_case_keys() {
+        let task = Task::Execute(Execute {
+            cmd: CmdArgs::Single("pytest".to_string()),
+            depends_on: vec![name("build")],
+            args: None,
+            inputs: None,
+            outputs: None,
+            cwd: None,
+            env: None,
+            clean_env: true,
+        });
+
+        let entry = TaskListEntry::from_task(&name("test"), &task);
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["depends-on"], serde_json::json!(["build"]));
+        assert_eq!(json["clean-env"], serde_json::json!(true));
+        assert!(json.get("depends_on").is_none());
+        assert!(json.get("clean_env").is_none());
+    }
+}
@@ -0,0 +1,326 @@
+use super::{Execute, TaskName};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use std::{fs, io};
+
+/// Whether a task needs to run, or can be skipped because its inputs, command and outputs are
+/// unchanged since the last successful run.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TaskStatus {
+    /// No cached fingerprint, or the inputs/command/outputs changed: the task must run.
+    Stale,
+    /// The fingerprint is unchanged and all declared outputs still exist: the task can be
+    /// skipped.
+    UpToDate,
+}
+
+/// A snapshot of a task's inputs (content hash + mtime, per resolved file) and the rendered
+/// command that last produced its outputs.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+struct Fingerprint {
+    command_hash: u64,
+    inputs: BTreeMap<String, FileStamp>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+struct FileStamp {
+    mtime_secs: u64,
+    content_hash: u64,
+}
+
+impl Fingerprint {
+    /// Computes the current fingerprint of `task` rooted at `project_root`, expanding
+    /// `task.inputs` globs relative to it.
+    fn compute(project_root: &Path, task: &Execute, rendered_cmd: &str) -> io::Result<Self> {
+        let mut command_hasher = DefaultHasher::new();
+        rendered_cmd.hash(&mut command_hasher);
+
+        let mut inputs = BTreeMap::new();
+        for pattern in task.inputs.iter().flatten() {
+            for entry in glob::glob(&project_root.join(pattern).to_string_lossy())
+                .into_iter()
+                .flatten()
+                .flatten()
+            {
+                let metadata = fs::metadata(&entry)?;
+                let mtime_secs = metadata
+                    .modified()?
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let content_hash = {
+                    let mut hasher = DefaultHasher::new();
+                    fs::read(&entry)?.hash(&mut hasher);
+                    hasher.finish()
+                };
+                inputs.insert(
+                    entry.to_string_lossy().to_string(),
+                    FileStamp {
+                        mtime_secs,
+                        content_hash,
+                    },
+                );
+            }
+        }
+
+        Ok(Self {
+            command_hash: command_hasher.finish(),
+            inputs,
+        })
+    }
+}
+
+/// The on-disk fingerprint cache for a project's tasks, stored as JSON under `.pixi/task-cache.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskCache {
+    #[serde(default)]
+    tasks: BTreeMap<String, Fingerprint>,
+}
+
+impl TaskCache {
+    /// Loads the cache from `path`, returning an empty cache if it doesn't exist yet or is
+    /// corrupt.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the cache to `path`, creating its parent directory (typically `.pixi`) if needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_vec_pretty(self).expect("TaskCache is always serializable");
+        fs::write(path, contents)
+    }
+
+    /// Determines whether `name` can be skipped: its inputs and rendered command must be
+    /// unchanged since the last recorded run, and every declared output
+    /// pattern must still have a match on disk. Passing `force` always reports [`TaskStatus::Stale`],
+    /// bypassing the cache entirely.
+    pub fn status(
+        &self,
+        project_root: &Path,
+        name: &TaskName,
+        task: &Execute,
+        rendered_cmd: &str,
+        force: bool,
+    ) -> io::Result<TaskStatus> {
+        if force {
+            return Ok(TaskStatus::Stale);
+        }
+        let Some(cached) = self.tasks.get(name.as_str()) else {
+            return Ok(TaskStatus::Stale);
+        };
+        let current = Fingerprint::compute(project_root, task, rendered_cmd)?;
+        if &current != cached {
+            return Ok(TaskStatus::Stale);
+        }
+        let outputs_exist = task.outputs.iter().flatten().all(|pattern| {
+            glob::glob(&project_root.join(pattern).to_string_lossy())
+                .into_iter()
+                .flatten()
+                .flatten()
+                .next()
+                .is_some()
+        });
+        Ok(if outputs_exist {
+            TaskStatus::UpToDate
+        } else {
+            TaskStatus::Stale
+        })
+    }
+
+    /// Records the fingerprint of `name` after it ran successfully, so the next run can skip it
+    /// if nothing relevant changed. Invalidates every task named in `downstream`, since a
+    /// dependency that actually re-ran may have changed an output that a downstream task reads
+    /// as an input.
+    pub fn record(
+        &mut self,
+        project_root: &Path,
+        name: &TaskName,
+        task: &Execute,
+        rendered_cmd: &str,
+        downstream: impl IntoIterator<Item = TaskName>,
+    ) -> io::Result<()> {
+        let fingerprint = Fingerprint::compute(project_root, task, rendered_cmd)?;
+        self.tasks.insert(name.as_str().to_string(), fingerprint);
+        for dependent in downstream {
+            self.tasks.remove(dependent.as_str());
+        }
+        Ok(())
+    }
+
+    /// Records `name`'s fingerprint after it actually ran (bypassing the cache because `force`
+    /// was passed, or because it was genuinely stale), invalidating every task in `graph` that
+    /// transitively depends on it.
+    pub fn record_and_invalidate_downstream(
+        &mut self,
+        project_root: &Path,
+        graph: &super::TaskGraph,
+        name: &TaskName,
+        task: &Execute,
+        rendered_cmd: &str,
+    ) -> io::Result<()> {
+        self.record(
+            project_root,
+            name,
+            task,
+            rendered_cmd,
+            graph.transitive_dependents(name),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::TaskGraph;
+    use std::fs;
+
+    fn name(s: &str) -> TaskName {
+        TaskName::from(s)
+    }
+
+    fn execute(inputs: &[&str], outputs: &[&str]) -> Execute {
+        Execute {
+            cmd: super::super::CmdArgs::Single("build".to_string()),
+            depends_on: Vec::new(),
+            args: None,
+            inputs: (!inputs.is_empty())
+                .then(|| inputs.iter().map(|s| s.to_string()).collect()),
+            outputs: (!outputs.is_empty())
+                .then(|| outputs.iter().map(|s| s.to_string()).collect()),
+            cwd: None,
+            env: None,
+            clean_env: false,
+        }
+    }
+
+    fn tempdir() -> std::path::PathBuf {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let dir = std::env::temp_dir().join(format!(
+            "pixi-task-cache-test-{}",
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn status_is_stale_without_a_recorded_fingerprint() {
+        let root = tempdir();
+        let cache = TaskCache::default();
+        let task = execute(&[], &[]);
+
+        let status = cache
+            .status(&root, &name("build"), &task, "build", false)
+            .expect("no i/o involved");
+        assert_eq!(status, TaskStatus::Stale);
+    }
+
+    #[test]
+    fn status_is_up_to_date_after_record_when_nothing_changed() {
+        let root = tempdir();
+        let output = root.join("out.txt");
+        fs::write(&output, "built").unwrap();
+        let task = execute(&[], &["out.txt"]);
+
+        let mut cache = TaskCache::default();
+        cache
+            .record(&root, &name("build"), &task, "build", std::iter::empty())
+            .expect("recording succeeds");
+
+        let status = cache
+            .status(&root, &name("build"), &task, "build", false)
+            .expect("no i/o involved");
+        assert_eq!(status, TaskStatus::UpToDate);
+    }
+
+    #[test]
+    fn status_is_stale_when_an_output_pattern_no_longer_matches() {
+        let root = tempdir();
+        let task = execute(&[], &["missing.txt"]);
+
+        let mut cache = TaskCache::default();
+        cache
+            .record(&root, &name("build"), &task, "build", std::iter::empty())
+            .expect("recording succeeds");
+
+        let status = cache
+            .status(&root, &name("build"), &task, "build", false)
+            .expect("no i/o involved");
+        assert_eq!(status, TaskStatus::Stale);
+    }
+
+    #[test]
+    fn status_is_stale_when_the_rendered_command_changed() {
+        let root = tempdir();
+        let task = execute(&[], &[]);
+
+        let mut cache = TaskCache::default();
+        cache
+            .record(&root, &name("build"), &task, "build v1", std::iter::empty())
+            .expect("recording succeeds");
+
+        let status = cache
+            .status(&root, &name("build"), &task, "build v2", false)
+            .expect("no i/o involved");
+        assert_eq!(status, TaskStatus::Stale);
+    }
+
+    #[test]
+    fn force_always_reports_stale() {
+        let root = tempdir();
+        let task = execute(&[], &[]);
+
+        let mut cache = TaskCache::default();
+        cache
+            .record(&root, &name("build"), &task, "build", std::iter::empty())
+            .expect("recording succeeds");
+
+        let status = cache
+            .status(&root, &name("build"), &task, "build", true)
+            .expect("no i/o involved");
+        assert_eq!(status, TaskStatus::Stale);
+    }
+
+    #[test]
+    fn record_and_invalidate_downstream_evicts_transitive_dependents() {
+        let root = tempdir();
+        let build = execute(&[], &[]);
+        let test = execute(&[], &[]);
+        let graph = TaskGraph::new([(name("build"), vec![]), (name("test"), vec![name("build")])]);
+
+        let mut cache = TaskCache::default();
+        cache
+            .record(&root, &name("test"), &test, "run-tests", std::iter::empty())
+            .expect("recording succeeds");
+        assert_eq!(
+            cache
+                .status(&root, &name("test"), &test, "run-tests", false)
+                .expect("no i/o involved"),
+            TaskStatus::UpToDate
+        );
+
+        cache
+            .record_and_invalidate_downstream(&root, &graph, &name("build"), &build, "build")
+            .expect("recording succeeds");
+
+        assert_eq!(
+            cache
+                .status(&root, &name("test"), &test, "run-tests", false)
+                .expect("no i/o involved"),
+            TaskStatus::Stale,
+            "test depends on build, so build re-running must invalidate it"
+        );
+    }
+}
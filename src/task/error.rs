@@ -0,0 +1,30 @@
+use miette::Diagnostic;
+use thiserror::Error;
+
+/// A declared task argument was not given a value at invocation time and has no default.
+#[derive(Debug, Clone, Error, Diagnostic)]
+#[error("missing value for argument '{name}'")]
+#[diagnostic(help("pass `--arg {name}=<value>` or declare a default when adding the task"))]
+pub struct InvalidArgument {
+    pub name: String,
+}
+
+/// A task's command references a `{{ name }}` placeholder that wasn't declared as an argument.
+#[derive(Debug, Clone, Error, Diagnostic)]
+#[error("command references undeclared argument '{{{{ {name} }}}}'")]
+#[diagnostic(help("declare it with `--arg {name}[=default]` when adding the task"))]
+pub struct InvalidArgRef {
+    pub name: String,
+}
+
+/// A task references `${name}` (or `$name`) but the variable is unset in both the activated
+/// pixi environment and, unless the task is `clean-env`, the outer shell, and has no
+/// `${name:-default}` fallback.
+#[derive(Debug, Clone, Error, Diagnostic)]
+#[error("environment variable '{name}' is not set and has no default")]
+#[diagnostic(help(
+    "set the variable before running `pixi run`, or add a fallback with `${{{name}:-default}}`"
+))]
+pub struct UnresolvedEnvVar {
+    pub name: String,
+}
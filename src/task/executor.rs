@@ -0,0 +1,187 @@
+use super::graph::{GraphError, TaskGraph};
+use super::TaskName;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Whether a task's output should be streamed straight to the console or buffered and
+/// prefixed, based on how many tasks are runnable at the same time.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OutputMode {
+    /// Only one task is runnable right now: stream its stdout/stderr directly.
+    Streamed,
+    /// Multiple tasks are runnable concurrently: buffer output and prefix it per task so
+    /// interleaved logs stay readable.
+    Buffered,
+}
+
+/// The result of running a [`TaskGraph`] to completion.
+#[derive(Debug, Clone, Default)]
+pub struct RunOutcome {
+    /// Tasks that ran and succeeded, in the order a batch of them finished.
+    pub completed: Vec<TaskName>,
+    /// The first task that failed, if any.
+    pub failed: Option<TaskName>,
+    /// Tasks that had not started when a task failed, whether or not they depend on it.
+    pub skipped: Vec<TaskName>,
+}
+
+/// Runs every task in `graph` to completion, bounded by `jobs` concurrent tasks (falling back
+/// to [`std::thread::available_parallelism`] when `None`), using a rayon worker pool. A task
+/// only starts once all of its dependencies have completed successfully; if a task fails, tasks
+/// that have not yet started are cancelled and reported as skipped.
+///
+/// `run_one` is called with the task about to run and the [`OutputMode`] it should use, and
+/// must return whether the task succeeded.
+pub fn run_graph<F>(
+    graph: &TaskGraph,
+    jobs: Option<usize>,
+    run_one: F,
+) -> Result<RunOutcome, GraphError>
+where
+    F: Fn(&TaskName, OutputMode) -> bool + Send + Sync,
+{
+    // Validate the graph up front so cycles are reported before any task runs.
+    graph.topological_order()?;
+
+    let jobs = jobs
+        .or_else(|| std::thread::available_parallelism().ok().map(Into::into))
+        .unwrap_or(1)
+        .max(1);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .expect("failed to build task worker pool");
+
+    let mut remaining: HashMap<TaskName, usize> = graph
+        .tasks()
+        .map(|name| (name.clone(), graph.depends_on(name).len()))
+        .collect();
+    let mut dependents: HashMap<TaskName, Vec<TaskName>> = HashMap::new();
+    for name in graph.tasks() {
+        for dep in graph.depends_on(name) {
+            dependents.entry(dep.clone()).or_default().push(name.clone());
+        }
+    }
+
+    let mut outcome = RunOutcome::default();
+    let mut ready: Vec<TaskName> = remaining
+        .iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    while !ready.is_empty() {
+        let mode = if ready.len() == 1 {
+            OutputMode::Streamed
+        } else {
+            OutputMode::Buffered
+        };
+
+        let results: Vec<(TaskName, bool)> = pool.install(|| {
+            ready
+                .par_iter()
+                .map(|name| (name.clone(), run_one(name, mode)))
+                .collect()
+        });
+
+        ready.clear();
+        let mut batch_failed = false;
+        for (name, succeeded) in results {
+            if succeeded {
+                for dependent in dependents.get(&name).into_iter().flatten() {
+                    let count = remaining.get_mut(dependent).expect("dependent in graph");
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(dependent.clone());
+                    }
+                }
+                outcome.completed.push(name);
+            } else {
+                batch_failed = true;
+                outcome.failed.get_or_insert(name);
+            }
+        }
+
+        if batch_failed {
+            break;
+        }
+    }
+
+    if outcome.failed.is_some() {
+        let completed: HashSet<&TaskName> = outcome.completed.iter().collect();
+        outcome.skipped = remaining
+            .keys()
+            .filter(|name| !completed.contains(name) && Some(*name) != outcome.failed.as_ref())
+            .cloned()
+            .collect();
+    }
+
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn name(s: &str) -> TaskName {
+        TaskName::from(s)
+    }
+
+    #[test]
+    fn runs_every_task_in_dependency_order() {
+        let graph = TaskGraph::new([
+            (name("a"), vec![]),
+            (name("b"), vec![name("a")]),
+            (name("c"), vec![name("a")]),
+        ]);
+
+        let outcome = run_graph(&graph, Some(2), |_, _| true).expect("acyclic graph");
+        assert_eq!(outcome.completed.len(), 3);
+        assert!(outcome.failed.is_none());
+        assert!(outcome.skipped.is_empty());
+    }
+
+    #[test]
+    fn failure_skips_not_yet_started_dependents() {
+        let graph = TaskGraph::new([
+            (name("a"), vec![]),
+            (name("b"), vec![name("a")]),
+            (name("c"), vec![name("b")]),
+        ]);
+
+        let outcome =
+            run_graph(&graph, Some(1), |task, _| task.as_str() != "b").expect("acyclic graph");
+        assert_eq!(outcome.failed, Some(name("b")));
+        assert_eq!(outcome.skipped, vec![name("c")]);
+    }
+
+    #[test]
+    fn streams_a_single_ready_task_even_while_others_are_still_blocked() {
+        // `a` and `d` are both roots; `d` unblocks `c` while `b` (which depends on `c`) is still
+        // blocked. Only `c` is ready in that batch, so it must stream even though the wider
+        // graph isn't quiescent yet.
+        let graph = TaskGraph::new([
+            (name("a"), vec![]),
+            (name("d"), vec![]),
+            (name("c"), vec![name("d")]),
+            (name("b"), vec![name("c")]),
+        ]);
+
+        let modes = Mutex::new(Vec::new());
+        let outcome = run_graph(&graph, Some(1), |task, mode| {
+            modes.lock().unwrap().push((task.clone(), mode));
+            true
+        })
+        .expect("acyclic graph");
+
+        assert_eq!(outcome.completed.len(), 4);
+        let modes = modes.into_inner().unwrap();
+        let c_mode = modes
+            .iter()
+            .find(|(task, _)| task.as_str() == "c")
+            .map(|(_, mode)| *mode)
+            .expect("c ran");
+        assert_eq!(c_mode, OutputMode::Streamed);
+    }
+}
@@ -0,0 +1,246 @@
+use super::{Task, TaskName};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use miette::Diagnostic;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// A DAG over a set of resolved tasks, with an edge from a task to each task named in its
+/// `depends-on`.
+#[derive(Debug, Clone, Default)]
+pub struct TaskGraph {
+    edges: IndexMap<TaskName, Vec<TaskName>>,
+}
+
+/// The `depends-on` chain that closes a cycle, in the order it was discovered.
+#[derive(Debug, Clone, Error, Diagnostic)]
+#[error("cyclic task dependency: {}", .chain.iter().map(TaskName::as_str).join(" -> "))]
+pub struct CycleError {
+    pub chain: Vec<TaskName>,
+}
+
+/// A task's `depends-on` names a task that isn't itself a node in the graph, e.g. a typo or a
+/// task filtered out for the current platform.
+#[derive(Debug, Clone, Error, Diagnostic)]
+#[error("task `{task}` depends on unknown task `{dependency}`")]
+pub struct MissingDependencyError {
+    pub task: TaskName,
+    pub dependency: TaskName,
+}
+
+/// Errors that can occur while computing a [`TaskGraph`]'s topological order.
+#[derive(Debug, Clone, Error, Diagnostic)]
+pub enum GraphError {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Cycle(#[from] CycleError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MissingDependency(#[from] MissingDependencyError),
+}
+
+impl TaskGraph {
+    /// Builds a graph from `(task, depends_on)` pairs.
+    pub fn new(edges: impl IntoIterator<Item = (TaskName, Vec<TaskName>)>) -> Self {
+        Self {
+            edges: edges.into_iter().collect(),
+        }
+    }
+
+    /// Builds a graph from the resolved tasks of an environment, taking the `depends-on` edges
+    /// straight from each [`Task`].
+    pub fn from_tasks<'a>(tasks: impl IntoIterator<Item = (&'a TaskName, &'a Task)>) -> Self {
+        Self::new(
+            tasks
+                .into_iter()
+                .map(|(name, task)| (name.clone(), task.depends_on().to_vec())),
+        )
+    }
+
+    /// Returns the tasks this graph knows about, in insertion order.
+    pub fn tasks(&self) -> impl Iterator<Item = &TaskName> {
+        self.edges.keys()
+    }
+
+    /// Returns the tasks that `task` directly depends on.
+    pub fn depends_on(&self, task: &TaskName) -> &[TaskName] {
+        self.edges.get(task).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns every task that depends on `task`, directly or transitively.
+    pub fn transitive_dependents(&self, task: &TaskName) -> Vec<TaskName> {
+        let mut dependents: HashMap<&TaskName, Vec<&TaskName>> = HashMap::new();
+        for (name, deps) in &self.edges {
+            for dep in deps {
+                dependents.entry(dep).or_default().push(name);
+            }
+        }
+
+        let mut visited = std::collections::HashSet::new();
+        let mut stack = dependents.get(task).cloned().unwrap_or_default();
+        while let Some(name) = stack.pop() {
+            if visited.insert(name) {
+                stack.extend(dependents.get(name).into_iter().flatten());
+            }
+        }
+
+        visited.into_iter().cloned().collect()
+    }
+
+    /// Computes a topological order of the graph (dependencies before dependents) using Kahn's
+    /// algorithm, detecting and reporting cycles with the offending `depends-on` chain, and
+    /// reporting [`MissingDependencyError`] if a `depends-on` entry isn't itself a node in the
+    /// graph.
+    pub fn topological_order(&self) -> Result<Vec<TaskName>, GraphError> {
+        for (name, deps) in &self.edges {
+            for dep in deps {
+                if !self.edges.contains_key(dep) {
+                    return Err(MissingDependencyError {
+                        task: name.clone(),
+                        dependency: dep.clone(),
+                    }
+                    .into());
+                }
+            }
+        }
+
+        // remaining[name] = number of not-yet-ordered tasks that `name` still depends on.
+        let mut remaining: HashMap<&TaskName, usize> = self
+            .edges
+            .keys()
+            .map(|name| (name, self.depends_on(name).len()))
+            .collect();
+
+        // dependents[dep] = tasks that list `dep` in their `depends-on`, i.e. the reverse edges.
+        let mut dependents: HashMap<&TaskName, Vec<&TaskName>> = HashMap::new();
+        for (name, deps) in &self.edges {
+            for dep in deps {
+                dependents.entry(dep).or_default().push(name);
+            }
+        }
+
+        let mut ready: Vec<&TaskName> = remaining
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(name, _)| *name)
+            .sorted()
+            .collect();
+        let mut order = Vec::with_capacity(self.edges.len());
+
+        while let Some(name) = ready.pop() {
+            order.push(name.clone());
+            for dependent in dependents.get(name).into_iter().flatten() {
+                let count = remaining.entry(dependent).or_insert(0);
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(dependent);
+                }
+            }
+        }
+
+        if order.len() != self.edges.len() {
+            return Err(CycleError {
+                chain: self.find_cycle(&order),
+            }
+            .into());
+        }
+
+        Ok(order)
+    }
+
+    /// Walks `depends-on` edges from an arbitrary remaining node until a node repeats, returning
+    /// the chain that closes the cycle.
+    fn find_cycle(&self, ordered: &[TaskName]) -> Vec<TaskName> {
+        let is_remaining = |name: &TaskName| !ordered.contains(name);
+        let mut visited = Vec::new();
+        let mut current = self
+            .edges
+            .keys()
+            .find(|name| is_remaining(name))
+            .expect("a cycle leaves tasks unordered");
+        loop {
+            if let Some(pos) = visited.iter().position(|name| name == current) {
+                visited.push(current.clone());
+                return visited[pos..].to_vec();
+            }
+            visited.push(current.clone());
+            current = self
+                .depends_on(current)
+                .iter()
+                .find(|dep| is_remaining(dep))
+                .expect("an unordered task has an unordered dependency");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(s: &str) -> TaskName {
+        TaskName::from(s)
+    }
+
+    #[test]
+    fn topological_order_respects_dependencies() {
+        let graph = TaskGraph::new([
+            (name("build"), vec![]),
+            (name("test"), vec![name("build")]),
+            (name("lint"), vec![name("build")]),
+            (name("ci"), vec![name("test"), name("lint")]),
+        ]);
+
+        let order = graph.topological_order().expect("acyclic graph");
+        let pos = |n: &str| order.iter().position(|t| t.as_str() == n).unwrap();
+
+        assert_eq!(order.len(), 4);
+        assert!(pos("build") < pos("test"));
+        assert!(pos("build") < pos("lint"));
+        assert!(pos("test") < pos("ci"));
+        assert!(pos("lint") < pos("ci"));
+    }
+
+    #[test]
+    fn topological_order_detects_cycle() {
+        let graph = TaskGraph::new([
+            (name("a"), vec![name("b")]),
+            (name("b"), vec![name("c")]),
+            (name("c"), vec![name("a")]),
+        ]);
+
+        let GraphError::Cycle(err) = graph.topological_order().expect_err("cyclic graph") else {
+            panic!("`a -> b -> c -> a` is a cycle, not a missing dependency");
+        };
+        assert_eq!(err.chain.len(), 4);
+        assert_eq!(err.chain.first(), err.chain.last());
+    }
+
+    #[test]
+    fn topological_order_reports_missing_dependency_not_a_cycle() {
+        let graph = TaskGraph::new([(name("a"), vec![name("ghost")])]);
+
+        let err = graph.topological_order().expect_err("unknown dependency");
+        match err {
+            GraphError::MissingDependency(err) => {
+                assert_eq!(err.task, name("a"));
+                assert_eq!(err.dependency, name("ghost"));
+            }
+            GraphError::Cycle(_) => panic!("`a -> ghost` is a missing dependency, not a cycle"),
+        }
+    }
+
+    #[test]
+    fn transitive_dependents_includes_indirect_dependents() {
+        let graph = TaskGraph::new([
+            (name("build"), vec![]),
+            (name("test"), vec![name("build")]),
+            (name("ci"), vec![name("test")]),
+            (name("unrelated"), vec![]),
+        ]);
+
+        let mut dependents = graph.transitive_dependents(&name("build"));
+        dependents.sort();
+        assert_eq!(dependents, vec![name("ci"), name("test")]);
+        assert!(graph.transitive_dependents(&name("unrelated")).is_empty());
+    }
+}
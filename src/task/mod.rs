@@ -0,0 +1,486 @@
+mod cache;
+mod error;
+mod executor;
+mod graph;
+mod resolve_env;
+
+pub use cache::{TaskCache, TaskStatus};
+pub use error::{InvalidArgRef, InvalidArgument, UnresolvedEnvVar};
+pub use executor::{run_graph, OutputMode, RunOutcome};
+pub use graph::{CycleError, GraphError, MissingDependencyError, TaskGraph};
+pub use resolve_env::ResolveEnv;
+
+use indexmap::IndexMap;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The name of a task
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TaskName(String);
+
+impl TaskName {
+    /// Returns the name of the task as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Returns a styled representation of the task name for use in terminal output.
+    pub fn fancy_display(&self) -> console::StyledObject<&str> {
+        console::style(self.as_str()).cyan()
+    }
+}
+
+impl From<TaskName> for String {
+    fn from(name: TaskName) -> Self {
+        name.0
+    }
+}
+
+impl From<String> for TaskName {
+    fn from(name: String) -> Self {
+        Self(name)
+    }
+}
+
+impl From<&str> for TaskName {
+    fn from(name: &str) -> Self {
+        Self(name.to_string())
+    }
+}
+
+impl FromStr for TaskName {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl fmt::Display for TaskName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A task's command, which can either be a single string or a list of arguments that are
+/// joined together with a space when rendered.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CmdArgs {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl CmdArgs {
+    /// Returns the task as a single string representation, quoting any argument that contains
+    /// whitespace.
+    pub fn as_single_string(&self) -> String {
+        match self {
+            CmdArgs::Single(str) => str.clone(),
+            CmdArgs::Multiple(args) => args.iter().map(|arg| quote(arg)).join(" "),
+        }
+    }
+}
+
+/// A declared argument of a task. When `default` is `None` the argument must be supplied every
+/// time the task is invoked.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ArgSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// The parameters a task declares, keyed by name in declaration order.
+pub type TaskArgs = IndexMap<String, ArgSpec>;
+
+/// A task that can be executed by `pixi run`, consisting of a command and its configuration:
+/// its dependencies, working directory, environment variables and declared inputs/outputs.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Execute {
+    /// The command to execute
+    pub cmd: CmdArgs,
+
+    /// A list of the task names that this task depends on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<TaskName>,
+
+    /// The parameters that can be substituted into `cmd`, keyed by name, with an optional
+    /// default value used when the caller doesn't supply one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<TaskArgs>,
+
+    /// A list of glob patterns that should be watched for changes before this command is run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<Vec<String>>,
+
+    /// A list of glob patterns that are generated by this command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<String>>,
+
+    /// The working directory to run the command in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+
+    /// A list of environment variables to set before running the command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<IndexMap<String, String>>,
+
+    /// Isolate the task from the shell environment, and only use the pixi environment to run the
+    /// task
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub clean_env: bool,
+}
+
+impl Execute {
+    /// Renders the command of this task, substituting any `{{ name }}` placeholder with the
+    /// value supplied in `values`, falling back to the declared default. Returns
+    /// [`InvalidArgument`] when a declared argument has neither a supplied value nor a default,
+    /// and [`InvalidArgRef`] when the command references a placeholder that wasn't declared.
+    pub fn render_cmd(&self, values: &IndexMap<String, String>) -> Result<String, TaskArgError> {
+        let resolved = resolve_args(self.args.as_ref(), values)?;
+        render_placeholders(&self.cmd.as_single_string(), &resolved)
+    }
+
+    /// Expands `${VAR}` references in `cmd`/`env`/`cwd` via [`ResolveEnv::resolve_env`] and then
+    /// renders `{{ name }}` placeholders via [`Execute::render_cmd`], producing the command
+    /// string that should actually be executed.
+    pub fn prepare_cmd(
+        &self,
+        values: &IndexMap<String, String>,
+        env_fn: impl Fn(&str) -> Result<String, UnresolvedEnvVar>,
+    ) -> Result<String, TaskArgError> {
+        let mut resolved = self.clone();
+        resolved.resolve_env(env_fn)?;
+        resolved.render_cmd(values)
+    }
+}
+
+/// Resolves `declared` arguments against caller-supplied `values`, falling back to each
+/// argument's default. Returns [`InvalidArgument`] for any declared argument that ends up with
+/// neither a supplied value nor a default.
+fn resolve_args(
+    declared: Option<&TaskArgs>,
+    values: &IndexMap<String, String>,
+) -> Result<IndexMap<String, String>, TaskArgError> {
+    let mut resolved = IndexMap::new();
+    for (name, spec) in declared.into_iter().flatten() {
+        if let Some(value) = values.get(name) {
+            resolved.insert(name.clone(), value.clone());
+        } else if let Some(default) = &spec.default {
+            resolved.insert(name.clone(), default.clone());
+        } else {
+            return Err(TaskArgError::InvalidArgument(InvalidArgument {
+                name: name.clone(),
+            }));
+        }
+    }
+    Ok(resolved)
+}
+
+/// A resolved invocation of a task together with the argument values it should run with.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TaskRef {
+    pub id: TaskName,
+    pub args: IndexMap<String, String>,
+}
+
+impl TaskRef {
+    /// Resolves `task`'s declared `args` against the CLI-supplied `values`.
+    pub fn resolve(
+        id: TaskName,
+        task: &Task,
+        values: IndexMap<String, String>,
+    ) -> Result<Self, TaskArgError> {
+        let args = resolve_args(task.args(), &values)?;
+        Ok(Self { id, args })
+    }
+
+    /// Returns the subset of this invocation's resolved values that `dependency` also declares,
+    /// so a dependent task can forward `{{ arg }}` values down its `depends-on` chain.
+    pub fn forward_to(&self, dependency: &Task) -> IndexMap<String, String> {
+        let Some(declared) = dependency.args() else {
+            return IndexMap::new();
+        };
+        self.args
+            .iter()
+            .filter(|(name, _)| declared.contains_key(*name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// Resolves `root` and every task it transitively depends on into a flat, dependencies-first
+/// list of [`TaskRef`]s. Each task's resolved argument values are forwarded to its direct
+/// dependencies via [`TaskRef::forward_to`], so a `{{ arg }}` value supplied for `root` reaches a
+/// dependency that declares the same argument name only if every task between them declares it
+/// too.
+pub fn resolve_chain(
+    root: TaskName,
+    values: IndexMap<String, String>,
+    tasks: &IndexMap<TaskName, Task>,
+) -> Result<Vec<TaskRef>, TaskArgError> {
+    let mut order = Vec::new();
+    let mut seen = HashSet::new();
+    resolve_chain_rec(&root, values, tasks, &mut order, &mut seen)?;
+    Ok(order)
+}
+
+fn resolve_chain_rec(
+    name: &TaskName,
+    values: IndexMap<String, String>,
+    tasks: &IndexMap<TaskName, Task>,
+    order: &mut Vec<TaskRef>,
+    seen: &mut HashSet<TaskName>,
+) -> Result<(), TaskArgError> {
+    if !seen.insert(name.clone()) {
+        return Ok(());
+    }
+    let Some(task) = tasks.get(name) else {
+        // An unknown dependency is reported by graph validation elsewhere; nothing to resolve.
+        return Ok(());
+    };
+    let resolved = TaskRef::resolve(name.clone(), task, values)?;
+    for dependency in task.depends_on() {
+        let forwarded = tasks
+            .get(dependency)
+            .map(|dep| resolved.forward_to(dep))
+            .unwrap_or_default();
+        resolve_chain_rec(dependency, forwarded, tasks, order, seen)?;
+    }
+    order.push(resolved);
+    Ok(())
+}
+
+/// Renders `{{ name }}` placeholders in `template` using `values`, returning
+/// [`InvalidArgRef`] if a placeholder is not present in `values`.
+fn render_placeholders(
+    template: &str,
+    values: &IndexMap<String, String>,
+) -> Result<String, TaskArgError> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            rendered.push_str(rest);
+            return Ok(rendered);
+        };
+        rendered.push_str(&rest[..start]);
+        let name = rest[start + 2..start + end].trim();
+        let value = values.get(name).ok_or_else(|| {
+            TaskArgError::InvalidArgRef(InvalidArgRef {
+                name: name.to_string(),
+            })
+        })?;
+        rendered.push_str(value);
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Errors that can occur while preparing a task for execution: resolving its declared
+/// [`args`](Execute::args) and expanding the `${VAR}` references in its command.
+#[derive(Debug, Clone, thiserror::Error, miette::Diagnostic)]
+pub enum TaskArgError {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidArgument(#[from] InvalidArgument),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidArgRef(#[from] InvalidArgRef),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnresolvedEnvVar(#[from] UnresolvedEnvVar),
+}
+
+/// An alias for another task or a set of tasks
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Alias {
+    /// A list of the task names that this alias executes
+    pub depends_on: Vec<TaskName>,
+}
+
+/// A task that can be executed by `pixi run`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum Task {
+    Plain(String),
+    Execute(Execute),
+    Alias(Alias),
+    #[serde(skip)]
+    Custom,
+}
+
+impl Task {
+    /// Returns the list of tasks that this task depends on
+    pub fn depends_on(&self) -> &[TaskName] {
+        match self {
+            Task::Execute(execute) => &execute.depends_on,
+            Task::Alias(alias) => &alias.depends_on,
+            Task::Plain(_) | Task::Custom => &[],
+        }
+    }
+
+    /// Returns the arguments declared by this task, if any.
+    pub fn args(&self) -> Option<&TaskArgs> {
+        match self {
+            Task::Execute(execute) => execute.args.as_ref(),
+            Task::Plain(_) | Task::Alias(_) | Task::Custom => None,
+        }
+    }
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Task::Plain(cmd) => write!(f, "{}", cmd),
+            Task::Execute(execute) => {
+                write!(f, "{}", execute.cmd.as_single_string())?;
+                if !execute.depends_on.is_empty() {
+                    write!(
+                        f,
+                        ", depends-on = {}",
+                        execute.depends_on.iter().map(|n| n.as_str()).join(", ")
+                    )?;
+                }
+                if let Some(args) = &execute.args {
+                    write!(f, ", args = {}", args.keys().join(", "))?;
+                }
+                Ok(())
+            }
+            Task::Alias(alias) => {
+                write!(
+                    f,
+                    "depends-on = {}",
+                    alias.depends_on.iter().map(|n| n.as_str()).join(", ")
+                )
+            }
+            Task::Custom => Ok(()),
+        }
+    }
+}
+
+/// Quotes an argument if it contains whitespace so it can be safely concatenated into a single
+/// command string.
+pub fn quote(in_str: &str) -> Cow<'_, str> {
+    if in_str.chars().any(char::is_whitespace) {
+        Cow::Owned(format!("\"{}\"", in_str))
+    } else {
+        Cow::Borrowed(in_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(s: &str) -> TaskName {
+        TaskName::from(s)
+    }
+
+    fn execute(cmd: &str, args: &[(&str, Option<&str>)], depends_on: &[&str]) -> Task {
+        Task::Execute(Execute {
+            cmd: CmdArgs::Single(cmd.to_string()),
+            depends_on: depends_on.iter().map(|n| name(n)).collect(),
+            args: Some(
+                args.iter()
+                    .map(|(name, default)| {
+                        (
+                            name.to_string(),
+                            ArgSpec {
+                                default: default.map(str::to_string),
+                            },
+                        )
+                    })
+                    .collect(),
+            ),
+            inputs: None,
+            outputs: None,
+            cwd: None,
+            env: None,
+            clean_env: false,
+        })
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> IndexMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn resolve_args_falls_back_to_declared_default() {
+        let Task::Execute(task) = execute("echo {{ greeting }}", &[("greeting", Some("hi"))], &[])
+        else {
+            unreachable!()
+        };
+
+        let rendered = task.render_cmd(&IndexMap::new()).expect("default applies");
+        assert_eq!(rendered, "echo hi");
+    }
+
+    #[test]
+    fn resolve_args_errors_on_missing_required_argument() {
+        let Task::Execute(task) = execute("echo {{ greeting }}", &[("greeting", None)], &[])
+        else {
+            unreachable!()
+        };
+
+        let err = task
+            .render_cmd(&IndexMap::new())
+            .expect_err("no value and no default");
+        assert!(matches!(err, TaskArgError::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn render_cmd_errors_on_undeclared_placeholder() {
+        let Task::Execute(task) = execute("echo {{ ghost }}", &[], &[]) else {
+            unreachable!()
+        };
+
+        let err = task
+            .render_cmd(&IndexMap::new())
+            .expect_err("ghost isn't declared");
+        assert!(matches!(err, TaskArgError::InvalidArgRef(_)));
+    }
+
+    #[test]
+    fn forward_to_only_carries_over_args_the_dependency_declares() {
+        let root = TaskRef {
+            id: name("root"),
+            args: values(&[("shared", "1"), ("root-only", "2")]),
+        };
+        let dependency = execute("echo {{ shared }}", &[("shared", None)], &[]);
+
+        let forwarded = root.forward_to(&dependency);
+        assert_eq!(forwarded, values(&[("shared", "1")]));
+    }
+
+    #[test]
+    fn resolve_chain_orders_dependencies_before_the_task_and_forwards_args() {
+        let tasks: IndexMap<TaskName, Task> = [
+            (name("build"), execute("gcc {{ mode }}", &[("mode", None)], &[])),
+            (
+                name("test"),
+                execute("run-tests {{ mode }}", &[("mode", None)], &["build"]),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let chain = resolve_chain(name("test"), values(&[("mode", "release")]), &tasks)
+            .expect("acyclic chain");
+
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0].id, name("build"));
+        assert_eq!(chain[1].id, name("test"));
+        assert_eq!(chain[0].args, values(&[("mode", "release")]));
+    }
+}
@@ -0,0 +1,223 @@
+use super::{Alias, Execute, Task, UnresolvedEnvVar};
+
+/// Expands `${VAR}`, `$VAR` and `${VAR:-default}` references inside a task. Implementors walk
+/// every string component they own (the command, `env` values, `cwd`, ...) and substitute
+/// variables pulled from the activated pixi environment first and the outer shell second,
+/// unless `clean_env` suppresses the shell fallback.
+pub trait ResolveEnv {
+    /// Resolves all environment variable references in `self` in place, looking up variables
+    /// in the pixi environment via `env_fn` first. `env_fn` returns `Err` when the pixi
+    /// environment has no value for the variable, which falls through to the shell fallback.
+    /// Returns [`UnresolvedEnvVar`] if a referenced variable has neither a value nor a
+    /// `:-default` fallback.
+    fn resolve_env<F: Fn(&str) -> Result<String, UnresolvedEnvVar>>(
+        &mut self,
+        env_fn: F,
+    ) -> Result<(), UnresolvedEnvVar>;
+}
+
+impl ResolveEnv for Execute {
+    fn resolve_env<F: Fn(&str) -> Result<String, UnresolvedEnvVar>>(
+        &mut self,
+        env_fn: F,
+    ) -> Result<(), UnresolvedEnvVar> {
+        let clean_env = self.clean_env;
+        let lookup = |name: &str| lookup_var(name, clean_env, &env_fn);
+
+        self.cmd = match std::mem::replace(&mut self.cmd, super::CmdArgs::Single(String::new())) {
+            super::CmdArgs::Single(cmd) => super::CmdArgs::Single(expand(&cmd, lookup)?),
+            super::CmdArgs::Multiple(cmds) => super::CmdArgs::Multiple(
+                cmds.iter()
+                    .map(|cmd| expand(cmd, lookup))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+
+        if let Some(env) = &mut self.env {
+            for value in env.values_mut() {
+                *value = expand(value, lookup)?;
+            }
+        }
+
+        if let Some(cwd) = &self.cwd {
+            let expanded = expand(&cwd.to_string_lossy(), lookup)?;
+            self.cwd = Some(expanded.into());
+        }
+
+        Ok(())
+    }
+}
+
+impl ResolveEnv for Alias {
+    fn resolve_env<F: Fn(&str) -> Result<String, UnresolvedEnvVar>>(
+        &mut self,
+        _env_fn: F,
+    ) -> Result<(), UnresolvedEnvVar> {
+        // An alias has no command, env or cwd of its own to expand.
+        Ok(())
+    }
+}
+
+impl ResolveEnv for Task {
+    fn resolve_env<F: Fn(&str) -> Result<String, UnresolvedEnvVar>>(
+        &mut self,
+        env_fn: F,
+    ) -> Result<(), UnresolvedEnvVar> {
+        match self {
+            Task::Execute(execute) => execute.resolve_env(env_fn),
+            Task::Alias(alias) => alias.resolve_env(env_fn),
+            Task::Plain(_) | Task::Custom => Ok(()),
+        }
+    }
+}
+
+/// Looks up `name`, preferring the pixi environment (`env_fn`) and falling back to the outer
+/// shell unless `clean_env` suppresses that fallback.
+fn lookup_var<F: Fn(&str) -> Result<String, UnresolvedEnvVar>>(
+    name: &str,
+    clean_env: bool,
+    env_fn: &F,
+) -> Option<String> {
+    env_fn(name).ok().or_else(|| {
+        if clean_env {
+            None
+        } else {
+            std::env::var(name).ok()
+        }
+    })
+}
+
+
+/// Expands every `${VAR}`, `${VAR:-default}` and `$VAR` reference in `template` using `lookup`.
+fn expand(
+    template: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, UnresolvedEnvVar> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if c != '$' {
+            rendered.push(c);
+            continue;
+        }
+
+        match chars.peek() {
+            Some((_, '{')) => {
+                chars.next();
+                let start = i + 2;
+                let Some(end) = template[start..].find('}') else {
+                    rendered.push_str(&template[i..]);
+                    break;
+                };
+                let body = &template[start..start + end];
+                let (name, default) = match body.split_once(":-") {
+                    Some((name, default)) => (name, Some(default)),
+                    None => (body, None),
+                };
+                match lookup(name).or_else(|| default.map(str::to_string)) {
+                    Some(value) => rendered.push_str(&value),
+                    None => {
+                        return Err(UnresolvedEnvVar {
+                            name: name.to_string(),
+                        })
+                    }
+                }
+                for _ in 0..=end {
+                    chars.next();
+                }
+            }
+            _ => {
+                let name_start = i + 1;
+                let name_end = template[name_start..]
+                    .find(|c: char| !c.is_alphanumeric() && c != '_')
+                    .map(|offset| name_start + offset)
+                    .unwrap_or(template.len());
+                if name_end == name_start {
+                    // A lone `$` not followed by an identifier is passed through verbatim.
+                    rendered.push('$');
+                    continue;
+                }
+                let name = &template[name_start..name_end];
+                match lookup(name) {
+                    Some(value) => rendered.push_str(&value),
+                    None => {
+                        return Err(UnresolvedEnvVar {
+                            name: name.to_string(),
+                        })
+                    }
+                }
+                for _ in name_start..name_end {
+                    chars.next();
+                }
+            }
+        }
+    }
+
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::CmdArgs;
+
+    fn execute(cmd: &str, clean_env: bool) -> Execute {
+        Execute {
+            cmd: CmdArgs::Single(cmd.to_string()),
+            depends_on: Vec::new(),
+            args: None,
+            inputs: None,
+            outputs: None,
+            cwd: None,
+            env: None,
+            clean_env,
+        }
+    }
+
+    fn ok(value: &str) -> impl Fn(&str) -> Result<String, UnresolvedEnvVar> + '_ {
+        move |name: &str| {
+            if name == "FROM_PIXI_ENV" {
+                Ok(value.to_string())
+            } else {
+                Err(UnresolvedEnvVar {
+                    name: name.to_string(),
+                })
+            }
+        }
+    }
+
+    #[test]
+    fn expands_dollar_var_from_the_pixi_environment() {
+        let mut task = execute("echo $FROM_PIXI_ENV", false);
+        task.resolve_env(ok("hello")).expect("resolved");
+        assert_eq!(task.cmd, CmdArgs::Single("echo hello".to_string()));
+    }
+
+    #[test]
+    fn expands_braced_var_with_fallback_default_when_unset() {
+        let mut task = execute("echo ${MISSING:-fallback}", false);
+        task.resolve_env(ok("unused")).expect("default applies");
+        assert_eq!(task.cmd, CmdArgs::Single("echo fallback".to_string()));
+    }
+
+    #[test]
+    fn clean_env_suppresses_the_shell_fallback() {
+        std::env::set_var("PIXI_RESOLVE_ENV_TEST_VAR", "from-shell");
+
+        let mut clean = execute("echo $PIXI_RESOLVE_ENV_TEST_VAR", true);
+        let err = clean
+            .resolve_env(ok("unused"))
+            .expect_err("clean_env suppresses the shell fallback");
+        assert_eq!(err.name, "PIXI_RESOLVE_ENV_TEST_VAR");
+
+        let mut not_clean = execute("echo $PIXI_RESOLVE_ENV_TEST_VAR", false);
+        not_clean.resolve_env(ok("unused")).expect("shell fallback applies");
+        assert_eq!(
+            not_clean.cmd,
+            CmdArgs::Single("echo from-shell".to_string())
+        );
+
+        std::env::remove_var("PIXI_RESOLVE_ENV_TEST_VAR");
+    }
+}